@@ -0,0 +1,32 @@
+use argh::FromArgs;
+use camino::Utf8PathBuf;
+
+/// Generate machine-ready G-code from a forge file.
+#[derive(Debug, FromArgs)]
+pub struct Arguments {
+    #[argh(subcommand)]
+    pub command: CommandEnum,
+}
+
+#[derive(Debug, FromArgs)]
+#[argh(subcommand)]
+pub enum CommandEnum {
+    Build(BuildCommand),
+}
+
+/// Build the G-code files described by a forge file.
+#[derive(Debug, FromArgs)]
+#[argh(subcommand, name = "build")]
+pub struct BuildCommand {
+    /// path to the forge file describing the job.
+    #[argh(positional)]
+    pub forge_file_path: Utf8PathBuf,
+
+    /// directory that generated G-code (and debug output) is written to.
+    #[argh(option, short = 'o', default = "Utf8PathBuf::from(\".\")")]
+    pub target_directory: Utf8PathBuf,
+
+    /// write intermediate debug artifacts (SVG renders, etc.) alongside the G-code.
+    #[argh(switch)]
+    pub debug: bool,
+}
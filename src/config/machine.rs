@@ -10,7 +10,7 @@ use uom::si::{
 use nalgebra::Vector2;
 use serde::Deserialize;
 
-use crate::parsing::parse_quantity;
+use crate::{gcode_generation::GCodeFlavor, parsing::parse_quantity};
 
 #[derive(Debug, Deserialize)]
 pub struct Machine {
@@ -22,6 +22,20 @@ pub struct Machine {
     /// Configurations for materials and tools that can be used for cutting.
     pub cutting_configs: HashMap<String, JobConfig>,
 
+    /// Configurations for materials and tools that can be used for drilling.
+    #[serde(default)]
+    pub drilling_configs: HashMap<String, JobConfig>,
+
+    /// The G-code dialect this machine's controller expects.
+    #[serde(default)]
+    pub flavor: GCodeFlavor,
+
+    /// An optional Lua script run against the finished command stream before it's written out,
+    /// for machine-specific tweaks (tool-change macros, dwells, coordinate remaps) that don't
+    /// belong in the core generator.
+    #[serde(default)]
+    pub post_process_script: Option<Utf8PathBuf>,
+
     /// The safe working area of the machine.
     pub workspace_area: WorkspaceSize,
 }
@@ -49,16 +63,63 @@ pub struct JobConfig {
     #[serde(default = "distance_per_step_default")]
     pub distance_per_step: Length<uom::si::SI<f32>, f32>,
 
+    /// The thickness of the stock being machined. Used by cutting stages to know how deep a
+    /// through-cut needs to go and where tab height is measured from.
+    #[serde(default = "stock_thickness_default", deserialize_with = "parse_quantity")]
+    pub stock_thickness: Length<uom::si::SI<f32>, f32>,
+
+    /// How far to plunge per peck when drilling, before retracting to clear chips. Only used by
+    /// drilling stages.
+    #[serde(default = "peck_depth_default", deserialize_with = "parse_quantity")]
+    pub peck_depth: Length<uom::si::SI<f32>, f32>,
+
+    /// The maximum difference between a hole's diameter and a spindle bit's diameter for that
+    /// bit to be considered a match. Only used by drilling stages.
+    #[serde(default = "drill_tolerance_default", deserialize_with = "parse_quantity")]
+    pub drill_tolerance: Length<uom::si::SI<f32>, f32>,
+
+    /// How many isolation passes to rout around each trace when engraving with a V-bit or end
+    /// mill. A single pass just isolates the trace; additional passes widen the cleared gap so
+    /// copper between traces is actually removed rather than left as a thin groove.
+    #[serde(default = "isolation_passes_default")]
+    pub isolation_passes: u32,
+
+    /// The fraction of the tool diameter that successive isolation passes should overlap by, so
+    /// the cleared area has no uncut ridges between passes. `0.0` packs passes edge-to-edge;
+    /// values closer to `1.0` pack them tightly together.
+    #[serde(default = "overlap_default")]
+    pub overlap: f32,
+
     /// The power of the tool. The unit depends on the tool.
     #[serde(flatten)]
     pub tool_power: ToolConfig,
 }
 
+fn stock_thickness_default() -> Length<uom::si::SI<f32>, f32> {
+    Length::new::<millimeter>(1.6)
+}
+
+fn peck_depth_default() -> Length<uom::si::SI<f32>, f32> {
+    Length::new::<millimeter>(0.5)
+}
+
+fn drill_tolerance_default() -> Length<uom::si::SI<f32>, f32> {
+    Length::new::<millimeter>(0.05)
+}
+
+fn isolation_passes_default() -> u32 {
+    1
+}
+
+fn overlap_default() -> f32 {
+    0.3
+}
+
 fn distance_per_step_default() -> Length<uom::si::SI<f32>, f32> {
     Length::new::<millimeter>(0.1)
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Clone, Deserialize)]
 #[serde(untagged)]
 pub enum ToolConfig {
     Laser {
@@ -0,0 +1,60 @@
+use std::{collections::HashMap, fs};
+
+use anyhow::{Context, Result};
+use camino::{Utf8Path, Utf8PathBuf};
+use serde::Deserialize;
+
+pub mod machine;
+
+use machine::Machine;
+
+/// User-wide configuration, loaded from the platform config directory. This is distinct from a
+/// forge file: a forge file describes a single job, while this describes the machines available
+/// on the current computer so forge files don't need to repeat that setup.
+#[derive(Debug, Deserialize, Default)]
+pub struct Config {
+    /// Machines known to this computer, keyed by name.
+    #[serde(default)]
+    pub machines: HashMap<String, Machine>,
+
+    /// The machine config path (`name/profile`) to use for engraving when a forge file does not
+    /// specify one explicitly.
+    #[serde(default)]
+    pub default_engraver: Option<Utf8PathBuf>,
+
+    /// The machine config path (`name/profile`) to use for cutting when a forge file does not
+    /// specify one explicitly.
+    #[serde(default)]
+    pub default_cutter: Option<Utf8PathBuf>,
+
+    /// The machine config path (`name/profile`) to use for drilling when a forge file does not
+    /// specify one explicitly.
+    #[serde(default)]
+    pub default_drill: Option<Utf8PathBuf>,
+}
+
+impl Config {
+    /// Returns the path to the user config file, following platform conventions.
+    pub fn get_path() -> Result<Utf8PathBuf> {
+        let project_dirs = directories::ProjectDirs::from("", "", "pcb_forge")
+            .context("Could not determine the current user's config directory.")?;
+
+        Utf8PathBuf::from_path_buf(project_dirs.config_dir().join("config.toml"))
+            .map_err(|path| anyhow::anyhow!("Config path {:?} is not valid UTF-8.", path))
+    }
+
+    /// Loads the user config file, if one is present.
+    pub fn load() -> Result<Self> {
+        let path = Self::get_path()?;
+        Self::load_from_path(&path)
+    }
+
+    /// Loads a config file from a specific path, for testing or overrides.
+    pub fn load_from_path(path: &Utf8Path) -> Result<Self> {
+        let contents = fs::read_to_string(path)
+            .with_context(|| format!("Failed to read config file at {}.", path))?;
+
+        toml::from_str(&contents)
+            .with_context(|| format!("Failed to parse config file at {}.", path))
+    }
+}
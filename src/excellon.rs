@@ -0,0 +1,379 @@
+use std::{collections::HashMap, fs};
+
+use anyhow::{bail, Context, Result};
+use camino::Utf8Path;
+use nalgebra::Vector2;
+use uom::si::{angular_velocity::revolution_per_minute, length::millimeter, velocity::millimeter_per_minute};
+
+use crate::{
+    config::machine::{JobConfig, SpindleBit, ToolConfig},
+    gcode_generation::{Command, ToolSelection},
+};
+
+/// A single hole to be drilled, in millimeters.
+#[derive(Debug, Clone, Copy)]
+pub struct DrillHole {
+    pub position: Vector2<f32>,
+    pub diameter: f32,
+}
+
+/// Coordinate zero-suppression mode: whether trailing or leading zeros are omitted from
+/// fixed-width Excellon coordinates (set by a `%TZ`/`%LZ` statement).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ZeroMode {
+    Trailing,
+    Leading,
+}
+
+/// A parsed Excellon (`.drl`) drill file: every hole position paired with the diameter of the
+/// tool that drills it.
+#[derive(Debug, Default)]
+pub struct DrillFile {
+    pub holes: Vec<DrillHole>,
+}
+
+/// Loads and parses an Excellon drill file at `path`.
+pub fn load(path: &Utf8Path) -> Result<DrillFile> {
+    let contents =
+        fs::read_to_string(path).with_context(|| format!("Failed to read drill file {path}."))?;
+
+    let mut tools: HashMap<u32, f32> = HashMap::new();
+    let mut active_tool: Option<u32> = None;
+    let mut metric = true;
+    let mut zero_mode = ZeroMode::Trailing;
+    let mut in_header = true;
+    // Excellon coordinates are modal: a line that omits X or Y carries forward the last position
+    // on that axis rather than snapping it back to the origin.
+    let mut last_position = (0.0_f32, 0.0_f32);
+
+    let mut drill_file = DrillFile::default();
+
+    for line in contents.lines() {
+        let line = line.trim();
+        if line.is_empty() {
+            continue;
+        }
+
+        match line {
+            "M48" => {
+                in_header = true;
+                continue;
+            }
+            "%" | "M95" => {
+                in_header = false;
+                continue;
+            }
+            "METRIC" => {
+                metric = true;
+                continue;
+            }
+            "INCH" => {
+                metric = false;
+                continue;
+            }
+            "M30" => break,
+            _ => {}
+        }
+
+        if line.contains("TZ") {
+            zero_mode = ZeroMode::Trailing;
+        } else if line.contains("LZ") {
+            zero_mode = ZeroMode::Leading;
+        }
+
+        if in_header && line.starts_with('T') {
+            // Tool table entry, e.g. "T1C0.800" -> tool 1 is an 0.8mm drill.
+            if let Some((tool, diameter)) = parse_tool_definition(line) {
+                tools.insert(tool, if metric { diameter } else { diameter * 25.4 });
+            }
+            continue;
+        }
+
+        if !in_header && line.starts_with('T') {
+            // Tool selection, e.g. "T1".
+            if let Ok(tool) = line.trim_start_matches('T').parse::<u32>() {
+                active_tool = Some(tool);
+            }
+            continue;
+        }
+
+        if !in_header && (line.starts_with('X') || line.starts_with('Y')) {
+            let tool = active_tool.context("Drill coordinate given before any tool was selected.")?;
+            let diameter = *tools
+                .get(&tool)
+                .with_context(|| format!("Drill file references undefined tool T{tool}."))?;
+
+            let position = parse_coordinate(line, metric, zero_mode, last_position)?;
+            last_position = (position.x, position.y);
+            drill_file.holes.push(DrillHole { position, diameter });
+        }
+    }
+
+    Ok(drill_file)
+}
+
+/// Parses a tool table line such as `"T1C0.800"` into `(tool_number, diameter)`, with the
+/// diameter in the file's current unit.
+fn parse_tool_definition(line: &str) -> Option<(u32, f32)> {
+    let rest = line.strip_prefix('T')?;
+    let digits_end = rest.find(|c: char| !c.is_ascii_digit())?;
+    let tool: u32 = rest[..digits_end].parse().ok()?;
+
+    let rest = &rest[digits_end..];
+    let diameter_str = rest.strip_prefix('C')?;
+    let diameter_end = diameter_str
+        .find(|c: char| c.is_ascii_alphabetic())
+        .unwrap_or(diameter_str.len());
+    diameter_str[..diameter_end].parse().ok().map(|diameter| (tool, diameter))
+}
+
+/// Parses an `X..Y..` coordinate line into millimeters, honoring the active unit and
+/// zero-suppression mode. Excellon coordinates are fixed-point with an implicit decimal point
+/// (2.4 format for metric, 2.3 for imperial), controlled by the same trailing/leading-zero mode.
+///
+/// Coordinates are modal: a line that omits X or Y carries forward the corresponding axis of
+/// `last_position` (already in millimeters) instead of snapping that axis back to the origin.
+fn parse_coordinate(
+    line: &str,
+    metric: bool,
+    zero_mode: ZeroMode,
+    last_position: (f32, f32),
+) -> Result<Vector2<f32>> {
+    let x = match extract_axis(line, 'X') {
+        Some(token) => scale_axis(token, metric, zero_mode),
+        None => last_position.0,
+    };
+    let y = match extract_axis(line, 'Y') {
+        Some(token) => scale_axis(token, metric, zero_mode),
+        None => last_position.1,
+    };
+
+    Ok(Vector2::new(x, y))
+}
+
+/// Converts a single raw coordinate token (e.g. `"010000"` or `"1.25"`) to millimeters.
+///
+/// Many Excellon generators emit a literal decimal point regardless of the declared TZ/LZ mode,
+/// so a token containing `.` is taken verbatim and never run through the fixed-width
+/// zero-suppression scaling below.
+fn scale_axis(token: &str, metric: bool, zero_mode: ZeroMode) -> f32 {
+    let in_units = if token.contains('.') {
+        token.parse().unwrap_or(0.0)
+    } else {
+        let raw: f32 = token.parse().unwrap_or(0.0);
+        match zero_mode {
+            // Trailing-zero suppression: trailing (decimal) zeros were dropped, so the implicit
+            // decimal point sits after the usual 2 integer digits but *within* however many
+            // digits the token actually has, e.g. "0127" (4 digits) -> "01.27" = 1.27.
+            ZeroMode::Trailing => {
+                const INTEGER_DIGITS: usize = 2;
+                let decimal_digits = token.len().saturating_sub(INTEGER_DIGITS) as i32;
+                raw / 10f32.powi(decimal_digits)
+            }
+            // Leading-zero suppression: fixed-width integer digits with an implicit decimal
+            // point, 2.4 for metric / 2.3 for inch.
+            ZeroMode::Leading => raw / if metric { 10_000.0 } else { 1_000.0 },
+        }
+    };
+
+    if metric {
+        in_units
+    } else {
+        in_units * 25.4
+    }
+}
+
+fn extract_axis(line: &str, axis: char) -> Option<&str> {
+    let start = line.find(axis)? + 1;
+    let rest = &line[start..];
+    let end = rest
+        .find(|c: char| c == 'X' || c == 'Y' || c == 'Z')
+        .unwrap_or(rest.len());
+    Some(&rest[..end])
+}
+
+/// Groups holes by diameter, within a small tolerance so near-identical holes produced by
+/// floating point export share a single group.
+pub fn group_by_diameter(holes: &[DrillHole]) -> Vec<(f32, Vec<Vector2<f32>>)> {
+    const GROUP_TOLERANCE: f32 = 0.01;
+
+    let mut groups: Vec<(f32, Vec<Vector2<f32>>)> = Vec::new();
+
+    for hole in holes {
+        if let Some(group) = groups
+            .iter_mut()
+            .find(|(diameter, _)| (diameter - hole.diameter).abs() < GROUP_TOLERANCE)
+        {
+            group.1.push(hole.position);
+        } else {
+            groups.push((hole.diameter, vec![hole.position]));
+        }
+    }
+
+    groups
+}
+
+/// Finds the `SpindleBit::Drill` bit closest to `diameter`, rejecting bits outside
+/// `tolerance`.
+fn find_matching_bit<'a>(
+    tool_selection: &ToolSelection<'a>,
+    diameter: f32,
+    tolerance: f32,
+) -> Option<&'a SpindleBit> {
+    let ToolSelection::Spindle { spindle, .. } = tool_selection else {
+        return None;
+    };
+
+    spindle
+        .bits
+        .values()
+        .filter(|bit| matches!(bit, SpindleBit::Drill { .. }))
+        .min_by(|a, b| {
+            let diff = |bit: &SpindleBit| match bit {
+                SpindleBit::Drill { diameter: bit_diameter } => {
+                    (bit_diameter.get::<millimeter>() - diameter).abs()
+                }
+                SpindleBit::EndMill { .. } => f32::MAX,
+            };
+            diff(a).total_cmp(&diff(b))
+        })
+        .filter(|bit| match bit {
+            SpindleBit::Drill { diameter: bit_diameter } => {
+                (bit_diameter.get::<millimeter>() - diameter).abs() <= tolerance
+            }
+            SpindleBit::EndMill { .. } => false,
+        })
+}
+
+/// Generates peck-drilling G-code for every hole in `drill_file` and appends it to `gcode`.
+///
+/// Holes are grouped by diameter; each group is matched to the nearest `SpindleBit::Drill` bit
+/// on the active spindle (within `job_config.drill_tolerance`), and drilled with the selected
+/// bit's tool change logged as a comment since this crate does not yet model automatic tool
+/// changers.
+pub fn generate_drill_gcode(
+    drill_file: &DrillFile,
+    gcode: &mut Vec<Command>,
+    job_config: &JobConfig,
+    tool_selection: &ToolSelection<'_>,
+) -> Result<()> {
+    let safe_z = 5.0;
+
+    let ToolConfig::Drill {
+        spindle_rpm,
+        plunge_speed,
+    } = &job_config.tool_power
+    else {
+        bail!("Drilling requires a drill job config (spindle RPM, plunge speed).");
+    };
+
+    let stock_thickness = job_config.stock_thickness.get::<millimeter>();
+    let peck_depth = job_config.peck_depth.get::<millimeter>();
+    let plunge_speed = plunge_speed.get::<millimeter_per_minute>();
+    let tolerance = job_config.drill_tolerance.get::<millimeter>();
+
+    if peck_depth <= 0.0 {
+        bail!("Drill peck_depth must be positive, got {peck_depth}mm.");
+    }
+
+    gcode.push(Command::SpindleOn {
+        rpm: spindle_rpm.get::<revolution_per_minute>(),
+    });
+
+    for (diameter, positions) in group_by_diameter(&drill_file.holes) {
+        let bit = find_matching_bit(tool_selection, diameter, tolerance);
+        match bit {
+            Some(SpindleBit::Drill { diameter: bit_diameter }) => {
+                gcode.push(Command::Comment(format!(
+                    "drill group: {:.3}mm holes -> bit {:.3}mm",
+                    diameter,
+                    bit_diameter.get::<millimeter>()
+                )));
+            }
+            _ => {
+                log::warn!(
+                    "No drill bit within {tolerance:.3}mm of the {diameter:.3}mm hole group; drilling anyway."
+                );
+                gcode.push(Command::Comment(format!(
+                    "WARNING: no matching bit for {diameter:.3}mm holes",
+                )));
+            }
+        }
+
+        for position in positions {
+            gcode.push(Command::RapidMove {
+                x: position.x,
+                y: position.y,
+                z: safe_z,
+            });
+
+            let mut depth = peck_depth.min(stock_thickness);
+            loop {
+                gcode.push(Command::LinearMove {
+                    x: position.x,
+                    y: position.y,
+                    z: -depth,
+                    feed_rate: plunge_speed,
+                });
+                gcode.push(Command::RapidMove {
+                    x: position.x,
+                    y: position.y,
+                    z: safe_z,
+                });
+
+                if depth >= stock_thickness {
+                    break;
+                }
+                depth = (depth + peck_depth).min(stock_thickness);
+            }
+        }
+    }
+
+    gcode.push(Command::ToolOff);
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_coordinate_trailing_zero_suppression_metric() {
+        // 2.4 format: "0127" has its 2 trailing (decimal) zeros dropped from "012700".
+        let position = parse_coordinate("X0127Y0254", true, ZeroMode::Trailing, (0.0, 0.0)).unwrap();
+        assert!((position.x - 1.27).abs() < 1e-4);
+        assert!((position.y - 2.54).abs() < 1e-4);
+    }
+
+    #[test]
+    fn parse_coordinate_leading_zero_suppression_metric() {
+        // 2.4 format: "127" is right-aligned, representing "000127" -> 0.0127.
+        let position = parse_coordinate("X127Y254", true, ZeroMode::Leading, (0.0, 0.0)).unwrap();
+        assert!((position.x - 0.0127).abs() < 1e-5);
+        assert!((position.y - 0.0254).abs() < 1e-5);
+    }
+
+    #[test]
+    fn parse_coordinate_literal_decimal_point_bypasses_zero_suppression() {
+        // Some generators emit a literal decimal point regardless of the declared TZ/LZ mode.
+        let position = parse_coordinate("X1.25Y3.5", true, ZeroMode::Leading, (0.0, 0.0)).unwrap();
+        assert!((position.x - 1.25).abs() < 1e-5);
+        assert!((position.y - 3.5).abs() < 1e-5);
+    }
+
+    #[test]
+    fn parse_coordinate_is_modal_and_carries_forward_omitted_axes() {
+        let last = (1.27, 2.54);
+        let position = parse_coordinate("Y0508", true, ZeroMode::Trailing, last).unwrap();
+        assert!((position.x - last.0).abs() < 1e-4);
+        assert!((position.y - 5.08).abs() < 1e-4);
+    }
+
+    #[test]
+    fn parse_coordinate_inch_scales_to_millimeters() {
+        let position = parse_coordinate("X1.0", false, ZeroMode::Leading, (0.0, 0.0)).unwrap();
+        assert!((position.x - 25.4).abs() < 1e-4);
+    }
+}
+
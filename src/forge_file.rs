@@ -0,0 +1,137 @@
+use std::{collections::HashMap, fs};
+
+use anyhow::{Context, Result};
+use camino::{Utf8Path, Utf8PathBuf};
+use serde::Deserialize;
+use uom::si::length::Length;
+
+use crate::{config::machine::Machine, parsing::parse_quantity};
+
+/// A forge file: the description of a single job, naming the gerber/drill inputs, which machine
+/// profile to run them through, and the G-code files to produce.
+#[derive(Debug, Deserialize)]
+pub struct ForgeFile {
+    /// Machines defined inline in this forge file, keyed by name. Looked up before falling back
+    /// to the user's global config.
+    #[serde(default)]
+    pub machines: HashMap<String, Machine>,
+
+    /// The ordered steps that make up this job.
+    pub stages: Vec<Stage>,
+}
+
+impl ForgeFile {
+    pub fn load_from_path(path: &Utf8Path) -> Result<Self> {
+        let contents = fs::read_to_string(path)
+            .with_context(|| format!("Failed to read forge file at {path}."))?;
+
+        toml::from_str(&contents)
+            .with_context(|| format!("Failed to parse forge file at {path}."))
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(tag = "type")]
+pub enum Stage {
+    /// Engraves (or laser-cuts) a copper/mask layer described by a gerber file.
+    #[serde(rename = "engrave_mask")]
+    EngraveMask {
+        /// The `machine/profile` to engrave with. Falls back to the global default engraver.
+        machine_config: Option<Utf8PathBuf>,
+        gerber_file: Utf8PathBuf,
+        gcode_file: Utf8PathBuf,
+
+        /// Where this stage's toolpath should be anchored within the machine's workspace.
+        #[serde(default)]
+        origin: Origin,
+
+        /// If true, translate the generated toolpath so it sits at `origin` instead of just
+        /// validating it against the workspace bounds as generated.
+        #[serde(default)]
+        auto_fit: bool,
+    },
+
+    /// Cuts the board outline out of the stock, optionally leaving holding tabs.
+    #[serde(rename = "cut_board")]
+    CutBoard {
+        /// The `machine/profile` to cut with. Falls back to the global default cutter.
+        machine_config: Option<Utf8PathBuf>,
+        gcode_file: Utf8PathBuf,
+
+        /// The gerber file containing the board outline (edge-cut layer).
+        file: Utf8PathBuf,
+
+        /// How many holding tabs to leave evenly spaced around each closed outline.
+        #[serde(default)]
+        tab_count: u32,
+
+        /// The width of each holding tab, along the direction of travel.
+        #[serde(default = "default_tab_width", deserialize_with = "parse_quantity")]
+        tab_width: Length<uom::si::SI<f32>, f32>,
+
+        /// The height of uncut stock left under each tab, measured up from the bottom of the
+        /// board.
+        #[serde(default = "default_tab_height", deserialize_with = "parse_quantity")]
+        tab_height: Length<uom::si::SI<f32>, f32>,
+
+        /// Where this stage's toolpath should be anchored within the machine's workspace.
+        #[serde(default)]
+        origin: Origin,
+
+        /// If true, translate the generated toolpath so it sits at `origin` instead of just
+        /// validating it against the workspace bounds as generated.
+        #[serde(default)]
+        auto_fit: bool,
+    },
+
+    /// Drills holes described by an Excellon drill file.
+    #[serde(rename = "drill_holes")]
+    DrillHoles {
+        /// The `machine/profile` to drill with. Falls back to the global default drill profile.
+        machine_config: Option<Utf8PathBuf>,
+        gcode_file: Utf8PathBuf,
+
+        /// The Excellon drill file to read hole positions and tool sizes from.
+        file: Utf8PathBuf,
+
+        /// Where this stage's toolpath should be anchored within the machine's workspace.
+        #[serde(default)]
+        origin: Origin,
+
+        /// If true, translate the generated toolpath so it sits at `origin` instead of just
+        /// validating it against the workspace bounds as generated.
+        #[serde(default)]
+        auto_fit: bool,
+    },
+}
+
+/// Where a stage's toolpath bounding box should be anchored within the machine's workspace when
+/// `auto_fit` is enabled.
+#[derive(Debug, Clone, Copy, Deserialize, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum Origin {
+    /// Anchor the bounding box's bottom-left corner at the workspace's origin. This is a no-op
+    /// only if that corner already sits at the origin.
+    #[default]
+    BottomLeft,
+
+    /// Center the bounding box within the workspace.
+    Center,
+
+    /// Anchor the bounding box's bottom-left corner at an explicit offset from the workspace's
+    /// origin.
+    Offset {
+        #[serde(deserialize_with = "parse_quantity")]
+        x: Length<uom::si::SI<f32>, f32>,
+        #[serde(deserialize_with = "parse_quantity")]
+        y: Length<uom::si::SI<f32>, f32>,
+    },
+}
+
+fn default_tab_width() -> Length<uom::si::SI<f32>, f32> {
+    Length::new::<uom::si::length::millimeter>(2.0)
+}
+
+fn default_tab_height() -> Length<uom::si::SI<f32>, f32> {
+    Length::new::<uom::si::length::millimeter>(1.0)
+}
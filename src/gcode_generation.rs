@@ -0,0 +1,337 @@
+use std::fmt::Write as _;
+
+use anyhow::Result;
+use serde::Deserialize;
+
+use crate::config::machine::{LaserConfig, SpindleBit, SpindleConfig};
+
+/// The tool a toolpath is being generated for, resolved from the machine config. Carried
+/// alongside the G-code commands so generators can make tool-specific decisions (laser power vs.
+/// spindle RPM, bit diameter, etc.) without re-threading the whole config.
+#[derive(Debug, Clone, Copy)]
+pub enum ToolSelection<'a> {
+    Laser { laser: &'a LaserConfig },
+    Spindle {
+        spindle: &'a SpindleConfig,
+        bit: &'a SpindleBit,
+    },
+}
+
+/// The G-code controller a machine speaks, which decides how the neutral [`Command`] stream is
+/// finally spelled out as text.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Default)]
+pub enum GCodeFlavor {
+    /// grbl and grbl-derived controllers (most hobby laser cutters and CNC routers).
+    #[default]
+    #[serde(rename = "grbl")]
+    Grbl,
+
+    /// Marlin/RepRap, as used by most 3D printers repurposed for laser engraving.
+    #[serde(rename = "marlin")]
+    Marlin,
+
+    /// LinuxCNC, common on larger shop-built routers.
+    #[serde(rename = "linuxcnc")]
+    LinuxCnc,
+
+    /// Smoothieware, as used by some open-hardware CNC controller boards.
+    #[serde(rename = "smoothieware")]
+    Smoothieware,
+}
+
+impl GCodeFlavor {
+    /// Whether this controller accepts `G2`/`G3` circular interpolation, or needs arcs
+    /// linearized into short line segments instead.
+    fn supports_arcs(self) -> bool {
+        !matches!(self, GCodeFlavor::Marlin)
+    }
+
+    /// The delimiters a comment is wrapped in.
+    fn comment_delimiters(self) -> (&'static str, &'static str) {
+        match self {
+            GCodeFlavor::Marlin => ("(", ")"),
+            GCodeFlavor::Grbl | GCodeFlavor::LinuxCnc | GCodeFlavor::Smoothieware => (";", ""),
+        }
+    }
+
+    fn comment(self, text: &str) -> String {
+        let (open, close) = self.comment_delimiters();
+        format!("{open}{text}{close}")
+    }
+
+    /// The preamble written at the top of every file: unit selection, positioning mode, and
+    /// (for flavors that expect it) a homing cycle.
+    fn preamble(self) -> Vec<String> {
+        let mut lines = vec![
+            format!("G21 {}", self.comment("millimeters")),
+            format!("G90 {}", self.comment("absolute positioning")),
+        ];
+
+        if matches!(self, GCodeFlavor::Marlin) {
+            lines.push(format!("G28 {}", self.comment("home all axes")));
+        }
+
+        lines
+    }
+
+    /// The postamble written at the end of every file: a safety tool-off (in case a generator
+    /// forgot one) followed by the program-end code.
+    fn postamble(self) -> Vec<String> {
+        vec![
+            format!("M5 {}", self.comment("tool off")),
+            format!("M2 {}", self.comment("end of program")),
+        ]
+    }
+
+    fn laser_on(self, power: f32) -> String {
+        match self {
+            // Marlin/RepRap firmware conventionally drives a laser as a PWM-controlled fan
+            // output rather than a dynamic-power spindle command.
+            GCodeFlavor::Marlin => format!("M106 S{:.0}", (power.clamp(0.0, 1.0)) * 255.0),
+            GCodeFlavor::Grbl | GCodeFlavor::LinuxCnc | GCodeFlavor::Smoothieware => {
+                format!("M4 S{:.0}", power.clamp(0.0, 1.0) * 1000.0)
+            }
+        }
+    }
+
+    fn laser_off(self) -> &'static str {
+        match self {
+            GCodeFlavor::Marlin => "M107",
+            GCodeFlavor::Grbl | GCodeFlavor::LinuxCnc | GCodeFlavor::Smoothieware => "M5",
+        }
+    }
+
+    fn spindle_on(self, rpm: f32) -> String {
+        format!("M3 S{rpm:.0}")
+    }
+
+    fn spindle_off(self) -> &'static str {
+        "M5"
+    }
+}
+
+/// A single, machine-neutral motion or tool command.
+///
+/// Generators (gerber tracing, board cutting, drilling) only ever produce `Command`s; the
+/// controller-specific spelling of each one is decided later, in [`GCodeFile::to_string`].
+#[derive(Debug, Clone, PartialEq)]
+pub enum Command {
+    /// A non-cutting, maximum-speed repositioning move.
+    RapidMove { x: f32, y: f32, z: f32 },
+
+    /// A cutting move at the configured work/plunge speed.
+    LinearMove {
+        x: f32,
+        y: f32,
+        z: f32,
+        feed_rate: f32,
+    },
+
+    /// A circular cutting move from the current position to `(x, y, z)`, pivoting around
+    /// `(center_x, center_y)` (relative to the start point), in the direction given by
+    /// `clockwise`.
+    Arc {
+        x: f32,
+        y: f32,
+        z: f32,
+        center_x: f32,
+        center_y: f32,
+        clockwise: bool,
+        feed_rate: f32,
+    },
+
+    /// Turns the laser on at the given power (0.0-1.0 of its max power).
+    LaserOn { power: f32 },
+
+    /// Turns the spindle on, spinning clockwise at the given RPM.
+    SpindleOn { rpm: f32 },
+
+    /// Turns the active tool (laser or spindle) off.
+    ToolOff,
+
+    /// Pauses motion for the given number of seconds, e.g. to let a spindle spin up.
+    Dwell { seconds: f32 },
+
+    /// A human-readable comment, carried through to the output file verbatim.
+    Comment(String),
+}
+
+/// A set of commands destined for a single output file, along with the controller dialect they
+/// should be serialized for.
+pub struct GCodeFile {
+    commands: Vec<Command>,
+    flavor: GCodeFlavor,
+}
+
+impl GCodeFile {
+    pub fn new(commands: Vec<Command>, flavor: GCodeFlavor) -> Self {
+        Self { commands, flavor }
+    }
+
+    /// Serializes the command stream to G-code text in this file's flavor.
+    pub fn to_string(&self) -> Result<String> {
+        let mut output = String::new();
+
+        for line in self.flavor.preamble() {
+            writeln!(output, "{line}")?;
+        }
+
+        let mut position = (0.0_f32, 0.0_f32, 0.0_f32);
+        let mut active_laser_tool = false;
+
+        for command in &self.commands {
+            match command {
+                Command::RapidMove { x, y, z } => {
+                    writeln!(output, "G0 X{x:.4} Y{y:.4} Z{z:.4}")?;
+                    position = (*x, *y, *z);
+                }
+                Command::LinearMove { x, y, z, feed_rate } => {
+                    writeln!(output, "G1 X{x:.4} Y{y:.4} Z{z:.4} F{feed_rate:.1}")?;
+                    position = (*x, *y, *z);
+                }
+                Command::Arc {
+                    x,
+                    y,
+                    z,
+                    center_x,
+                    center_y,
+                    clockwise,
+                    feed_rate,
+                } => {
+                    if self.flavor.supports_arcs() {
+                        let word = if *clockwise { "G2" } else { "G3" };
+                        writeln!(
+                            output,
+                            "{word} X{x:.4} Y{y:.4} Z{z:.4} I{center_x:.4} J{center_y:.4} F{feed_rate:.1}"
+                        )?;
+                    } else {
+                        for (segment_x, segment_y, segment_z) in
+                            linearize_arc(position, (*x, *y, *z), (*center_x, *center_y), *clockwise)
+                        {
+                            writeln!(
+                                output,
+                                "G1 X{segment_x:.4} Y{segment_y:.4} Z{segment_z:.4} F{feed_rate:.1}"
+                            )?;
+                        }
+                    }
+                    position = (*x, *y, *z);
+                }
+                Command::LaserOn { power } => {
+                    active_laser_tool = true;
+                    writeln!(output, "{}", self.flavor.laser_on(*power))?;
+                }
+                Command::SpindleOn { rpm } => {
+                    active_laser_tool = false;
+                    writeln!(output, "{}", self.flavor.spindle_on(*rpm))?;
+                }
+                Command::ToolOff => writeln!(
+                    output,
+                    "{}",
+                    if active_laser_tool {
+                        self.flavor.laser_off()
+                    } else {
+                        self.flavor.spindle_off()
+                    }
+                )?,
+                Command::Dwell { seconds } => writeln!(output, "G4 P{seconds:.2}")?,
+                Command::Comment(text) => writeln!(output, "{}", self.flavor.comment(text))?,
+            }
+        }
+
+        for line in self.flavor.postamble() {
+            writeln!(output, "{line}")?;
+        }
+
+        Ok(output)
+    }
+}
+
+/// Approximates a circular arc as a sequence of `(x, y, z)` line-segment endpoints, for
+/// controllers that don't accept `G2`/`G3`.
+fn linearize_arc(
+    start: (f32, f32, f32),
+    end: (f32, f32, f32),
+    center_offset: (f32, f32),
+    clockwise: bool,
+) -> Vec<(f32, f32, f32)> {
+    const SEGMENTS: u32 = 16;
+
+    let center = (start.0 + center_offset.0, start.1 + center_offset.1);
+    let radius = ((start.0 - center.0).powi(2) + (start.1 - center.1).powi(2)).sqrt();
+
+    let start_angle = (start.1 - center.1).atan2(start.0 - center.0);
+    let mut end_angle = (end.1 - center.1).atan2(end.0 - center.0);
+
+    if clockwise && end_angle > start_angle {
+        end_angle -= std::f32::consts::TAU;
+    } else if !clockwise && end_angle < start_angle {
+        end_angle += std::f32::consts::TAU;
+    }
+
+    (1..=SEGMENTS)
+        .map(|step| {
+            let t = step as f32 / SEGMENTS as f32;
+            let angle = start_angle + (end_angle - start_angle) * t;
+            (
+                center.0 + radius * angle.cos(),
+                center.1 + radius * angle.sin(),
+                start.2 + (end.2 - start.2) * t,
+            )
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tool_off_emits_laser_off_for_a_laser_section_even_after_a_later_spindle_section() {
+        // Two stages sharing one file: a laser pass followed by a spindle pass. The first
+        // ToolOff (ending the laser section) must not be influenced by the later SpindleOn.
+        // Marlin is used because its laser-off (M107) differs from its spindle-off (M5); on
+        // flavors where both happen to be "M5" this distinction wouldn't be observable.
+        let file = GCodeFile::new(
+            vec![
+                Command::LaserOn { power: 1.0 },
+                Command::LinearMove { x: 1.0, y: 0.0, z: 0.0, feed_rate: 100.0 },
+                Command::ToolOff,
+                Command::SpindleOn { rpm: 10_000.0 },
+                Command::LinearMove { x: 2.0, y: 0.0, z: 0.0, feed_rate: 100.0 },
+                Command::ToolOff,
+            ],
+            GCodeFlavor::Marlin,
+        );
+
+        let output = file.to_string().unwrap();
+        // ToolOff is written bare (no comment suffix), unlike the safety M5 in the postamble, so
+        // an exact-line match isolates the two ToolOff commands from the postamble's own M5.
+        let tool_off_lines: Vec<&str> = output
+            .lines()
+            .filter(|&line| line == "M107" || line == "M5")
+            .collect();
+
+        assert_eq!(tool_off_lines, vec!["M107", "M5"]);
+    }
+
+    #[test]
+    fn tool_off_without_any_tool_on_defaults_to_spindle_off() {
+        let file = GCodeFile::new(vec![Command::ToolOff], GCodeFlavor::Grbl);
+        let output = file.to_string().unwrap();
+        assert!(output.lines().any(|line| line == "M5"));
+    }
+
+    #[test]
+    fn comment_delimiters_match_flavor() {
+        assert_eq!(GCodeFlavor::Marlin.comment("hi"), "(hi)");
+        assert_eq!(GCodeFlavor::Grbl.comment("hi"), ";hi");
+    }
+
+    #[test]
+    fn linearize_arc_ends_at_the_arc_endpoint() {
+        let segments = linearize_arc((0.0, 0.0, 0.0), (0.0, 10.0, 0.0), (0.0, 5.0), true);
+        let last = *segments.last().unwrap();
+        assert!((last.0 - 0.0).abs() < 1e-3);
+        assert!((last.1 - 10.0).abs() < 1e-3);
+    }
+}
@@ -0,0 +1,129 @@
+use nalgebra::Vector2;
+
+/// A closed, simple polygon in board space (millimeters). The points keep whatever winding they
+/// were given in; [`Polygon::wound_ccw`] returns a counter-clockwise copy.
+///
+/// This is the common currency between gerber parsing and toolpath generation: board-outline
+/// traces are stitched into polygons, which are then offset and walked to produce G-code motion.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Polygon {
+    pub points: Vec<Vector2<f32>>,
+}
+
+impl Polygon {
+    pub fn new(points: Vec<Vector2<f32>>) -> Self {
+        Self { points }
+    }
+
+    /// The total perimeter length of the closed contour.
+    pub fn perimeter(&self) -> f32 {
+        self.edges().map(|(a, b)| (b - a).norm()).sum()
+    }
+
+    /// Iterates over the polygon's edges as `(start, end)` point pairs, wrapping from the last
+    /// point back to the first.
+    pub fn edges(&self) -> impl Iterator<Item = (Vector2<f32>, Vector2<f32>)> + '_ {
+        let count = self.points.len();
+        (0..count).map(move |index| (self.points[index], self.points[(index + 1) % count]))
+    }
+
+    /// The signed area of the polygon (via the shoelace formula). Positive for
+    /// counter-clockwise winding.
+    pub fn signed_area(&self) -> f32 {
+        self.edges().map(|(a, b)| a.x * b.y - b.x * a.y).sum::<f32>() * 0.5
+    }
+
+    /// Returns a copy of this polygon with a consistent counter-clockwise winding.
+    pub fn wound_ccw(&self) -> Self {
+        if self.signed_area() < 0.0 {
+            let mut points = self.points.clone();
+            points.reverse();
+            Self::new(points)
+        } else {
+            self.clone()
+        }
+    }
+
+    /// Offsets every edge outward (away from the polygon's interior) by `distance`, producing a
+    /// new closed contour.
+    ///
+    /// This uses a simple per-vertex miter offset rather than a general polygon-clipping offset
+    /// algorithm: each vertex is pushed out along the bisector of its two adjacent edge normals.
+    /// That is sufficient for the convex-ish, gently-curved board and trace outlines this crate
+    /// deals with; self-intersecting offsets of sharply concave corners are not handled.
+    pub fn offset(&self, distance: f32) -> Self {
+        let ccw = self.wound_ccw();
+        let count = ccw.points.len();
+
+        let points = (0..count)
+            .map(|index| {
+                let previous = ccw.points[(index + count - 1) % count];
+                let current = ccw.points[index];
+                let next = ccw.points[(index + 1) % count];
+
+                let incoming_normal = outward_normal(previous, current);
+                let outgoing_normal = outward_normal(current, next);
+
+                let mut bisector = incoming_normal + outgoing_normal;
+                if bisector.norm_squared() < f32::EPSILON {
+                    bisector = incoming_normal;
+                } else {
+                    bisector = bisector.normalize();
+                }
+
+                // Scale so the offset edges, not the vertex itself, end up `distance` away.
+                let miter_scale = (2.0 / (1.0 + incoming_normal.dot(&outgoing_normal)).max(0.5)).sqrt();
+
+                current + bisector * distance * miter_scale
+            })
+            .collect();
+
+        Self::new(points)
+    }
+
+    /// Returns `count` evenly spaced segments along the perimeter, each `length` long,
+    /// expressed as a `(start, end)` point pair. Used to carve out holding-tab gaps.
+    pub fn evenly_spaced_segments(&self, count: u32, length: f32) -> Vec<(Vector2<f32>, Vector2<f32>)> {
+        if count == 0 {
+            return Vec::new();
+        }
+
+        let perimeter = self.perimeter();
+        let spacing = perimeter / count as f32;
+
+        (0..count)
+            .map(|index| {
+                let center_distance = spacing * index as f32;
+                let start = self.point_at_distance(center_distance - length / 2.0);
+                let end = self.point_at_distance(center_distance + length / 2.0);
+                (start, end)
+            })
+            .collect()
+    }
+
+    /// Walks the perimeter starting from the first point and returns the point `distance` along
+    /// it, wrapping around as needed (including negative distances).
+    pub fn point_at_distance(&self, distance: f32) -> Vector2<f32> {
+        let perimeter = self.perimeter();
+        let mut remaining = distance % perimeter;
+        if remaining < 0.0 {
+            remaining += perimeter;
+        }
+
+        for (start, end) in self.edges() {
+            let edge_length = (end - start).norm();
+            if remaining <= edge_length || edge_length == 0.0 {
+                return start + (end - start) * (remaining / edge_length.max(f32::EPSILON));
+            }
+            remaining -= edge_length;
+        }
+
+        self.points[0]
+    }
+}
+
+/// The outward-facing normal of the edge from `a` to `b`, assuming counter-clockwise winding.
+fn outward_normal(a: Vector2<f32>, b: Vector2<f32>) -> Vector2<f32> {
+    let direction = (b - a).normalize();
+    Vector2::new(direction.y, -direction.x)
+}
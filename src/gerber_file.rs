@@ -0,0 +1,664 @@
+use std::{collections::HashMap, fs};
+
+use anyhow::{bail, Context, Result};
+use camino::Utf8Path;
+use nalgebra::Vector2;
+use svg_composer::{element::Path, Document};
+use uom::si::{angular_velocity::revolution_per_minute, length::millimeter};
+
+use crate::{
+    config::machine::{JobConfig, SpindleBit, ToolConfig},
+    gcode_generation::{Command, ToolSelection},
+    geometry::Polygon,
+};
+
+/// A single draw command decoded from a Gerber file: a straight stroke from `start` to `end`
+/// with the aperture (tool) diameter active at the time, in millimeters.
+#[derive(Debug, Clone)]
+pub struct Trace {
+    pub start: Vector2<f32>,
+    pub end: Vector2<f32>,
+    pub width: f32,
+}
+
+/// A parsed, flattened Gerber layer: just the strokes needed to render and machine it. Gerber's
+/// region/aperture macro features are not modeled beyond what's needed to produce these strokes.
+#[derive(Debug, Default)]
+pub struct GerberFile {
+    pub traces: Vec<Trace>,
+}
+
+/// Loads and parses a Gerber (RS-274X) file at `path` into `gerber`.
+pub fn load(gerber: &mut GerberFile, path: &Utf8Path) -> Result<()> {
+    let contents =
+        fs::read_to_string(path).with_context(|| format!("Failed to read gerber file {path}."))?;
+
+    let mut apertures: HashMap<u32, f32> = HashMap::new();
+    let mut active_aperture: Option<u32> = None;
+    let mut position = Vector2::new(0.0_f32, 0.0_f32);
+    // The %FS format statement is not read: coordinates are always taken as integers in
+    // thousandths of the unit, which is millimeters unless a %MOIN statement switches to inches.
+    let mut unit_scale = 1.0_f32;
+
+    for statement in contents.split('*') {
+        let statement = statement.trim();
+        if statement.is_empty() {
+            continue;
+        }
+
+        if let Some(definition) = statement.strip_prefix("%ADD") {
+            // e.g. "10C,0.200" -> aperture 10 is a 0.2mm circle.
+            if let Some((number, shape)) = definition.split_once(',') {
+                let number: u32 = number
+                    .trim_end_matches(|c: char| !c.is_ascii_digit())
+                    .parse()
+                    .unwrap_or_default();
+                let diameter: f32 = shape
+                    .split('X')
+                    .next()
+                    .unwrap_or_default()
+                    .trim_end_matches('%')
+                    .parse()
+                    .unwrap_or(0.0);
+                apertures.insert(number, diameter * unit_scale);
+            }
+            continue;
+        }
+
+        if statement.contains("%MOMM") {
+            unit_scale = 1.0;
+            continue;
+        }
+        if statement.contains("%MOIN") {
+            unit_scale = 25.4;
+            continue;
+        }
+
+        if let Some(aperture) = statement.strip_prefix('D') {
+            if let Ok(number) = aperture.parse::<u32>() {
+                if apertures.contains_key(&number) {
+                    active_aperture = Some(number);
+                    continue;
+                }
+            }
+        }
+
+        let (coordinates, operation) = match statement.rsplit_once('D') {
+            Some((coordinates, operation)) => (coordinates, operation),
+            None => continue,
+        };
+
+        let mut next = position;
+        if let Some(x_start) = coordinates.find('X') {
+            let rest = &coordinates[x_start + 1..];
+            let end = rest.find('Y').unwrap_or(rest.len());
+            if let Ok(value) = rest[..end].parse::<f32>() {
+                next.x = value * unit_scale / 1_000_000.0 * 1000.0;
+            }
+        }
+        if let Some(y_start) = coordinates.find('Y') {
+            let rest = &coordinates[y_start + 1..];
+            if let Ok(value) = rest.parse::<f32>() {
+                next.y = value * unit_scale / 1_000_000.0 * 1000.0;
+            }
+        }
+
+        match operation {
+            "01" => {
+                let width = active_aperture.and_then(|a| apertures.get(&a)).copied().unwrap_or(0.0);
+                gerber.traces.push(Trace {
+                    start: position,
+                    end: next,
+                    width,
+                });
+                position = next;
+            }
+            "02" => position = next,
+            "03" => {
+                let width = active_aperture.and_then(|a| apertures.get(&a)).copied().unwrap_or(0.0);
+                gerber.traces.push(Trace {
+                    start: next,
+                    end: next,
+                    width,
+                });
+                position = next;
+            }
+            _ => {}
+        }
+    }
+
+    Ok(())
+}
+
+impl GerberFile {
+    /// Computes a `(min_x, min_y, width, height)` bounding box, in millimeters, suitable for an
+    /// SVG `viewBox`.
+    pub fn calculate_svg_bounds(&self) -> (f64, f64, f64, f64) {
+        if self.traces.is_empty() {
+            return (0.0, 0.0, 1.0, 1.0);
+        }
+
+        let (mut min_x, mut min_y) = (f32::MAX, f32::MAX);
+        let (mut max_x, mut max_y) = (f32::MIN, f32::MIN);
+
+        for trace in &self.traces {
+            for point in [trace.start, trace.end] {
+                min_x = min_x.min(point.x);
+                min_y = min_y.min(point.y);
+                max_x = max_x.max(point.x);
+                max_y = max_y.max(point.y);
+            }
+        }
+
+        (
+            min_x as f64,
+            min_y as f64,
+            (max_x - min_x) as f64,
+            (max_y - min_y) as f64,
+        )
+    }
+
+    /// Renders every trace as an SVG path, for human debugging of the parsed geometry.
+    ///
+    /// `simplified` only picks the stroke colour (blue when true, red when false), so the two
+    /// renders written when `--debug` is passed can be told apart; no traces are merged or
+    /// cleaned up here.
+    pub fn debug_render(&self, document: &mut Document, simplified: bool) -> Result<()> {
+        for trace in &self.traces {
+            let data = format!(
+                "M {} {} L {} {}",
+                trace.start.x, trace.start.y, trace.end.x, trace.end.y
+            );
+            let stroke = if simplified { "blue" } else { "red" };
+            document.add_element(Box::new(
+                Path::new()
+                    .set("d", data)
+                    .set("stroke", stroke)
+                    .set("stroke-width", trace.width.max(0.05).to_string())
+                    .set("fill", "none"),
+            ));
+        }
+
+        Ok(())
+    }
+
+    /// Groups traces into closed contours (polygons), matching consecutive traces whose
+    /// endpoints meet. Used for board-outline (edge-cut) geometry, where the layer is expected to
+    /// describe one or more closed loops rather than open copper traces.
+    pub fn closed_contours(&self) -> Vec<Polygon> {
+        const EPSILON: f32 = 1e-3;
+
+        let mut remaining: Vec<Trace> = self.traces.clone();
+        let mut contours = Vec::new();
+
+        while !remaining.is_empty() {
+            let first = remaining.remove(0);
+            let mut points = vec![first.start, first.end];
+
+            loop {
+                let tail = *points.last().unwrap();
+                let next_index = remaining
+                    .iter()
+                    .position(|trace| (trace.start - tail).norm() < EPSILON);
+
+                let Some(next_index) = next_index else {
+                    break;
+                };
+
+                let next = remaining.remove(next_index);
+                points.push(next.end);
+
+                if (points[0] - points.last().unwrap()).norm() < EPSILON {
+                    break;
+                }
+            }
+
+            let closed =
+                points.len() >= 3 && (points[0] - points.last().unwrap()).norm() < EPSILON;
+            if closed {
+                points.pop();
+                contours.push(Polygon::new(points));
+            }
+        }
+
+        contours
+    }
+
+    /// Generates the isolation-routing G-code for this layer and appends it to `gcode`.
+    ///
+    /// When cutting with a spindle end mill, this walks `job_config.isolation_passes` outward
+    /// offset contours per trace (each spaced by `tool_diameter * (1 - overlap)`), innermost
+    /// first, so the copper between traces is actually cleared rather than left with a single
+    /// thin isolation groove. `Trace` carries no electrical net identity (the Gerber parser
+    /// doesn't track `%TO.N`/net attributes), so passes are ordered per individual trace segment
+    /// rather than grouped by net; a net split across several disjoint segments is not routed as
+    /// one travel-minimizing unit. Lasers don't need offsetting (their kerf is already accounted
+    /// for by the gerber export), so they trace the centerline directly.
+    ///
+    /// `travel_between_traces` controls whether the tool is lifted and rapid-traversed between
+    /// disconnected traces (true for isolation milling) or kept engaged (not currently used, but
+    /// kept as a parameter so future continuous-cut stages can reuse this).
+    pub fn generate_gcode(
+        &self,
+        gcode: &mut Vec<Command>,
+        job_config: &JobConfig,
+        tool_selection: &ToolSelection<'_>,
+        travel_between_traces: bool,
+    ) -> Result<()> {
+        let safe_z = 5.0;
+
+        let power_or_rpm_on = match tool_selection {
+            ToolSelection::Laser { .. } => Command::LaserOn { power: 1.0 },
+            ToolSelection::Spindle { spindle, .. } => Command::SpindleOn {
+                rpm: spindle.max_speed.get::<uom::si::angular_velocity::revolution_per_minute>(),
+            },
+        };
+
+        let work_speed = match &job_config.tool_power {
+            ToolConfig::Laser { work_speed, .. } | ToolConfig::EndMill { work_speed, .. } => {
+                work_speed.get::<uom::si::velocity::millimeter_per_minute>()
+            }
+            ToolConfig::Drill { .. } => {
+                bail!("Engraving/isolation milling requires a laser or end-mill job config, not a drill.")
+            }
+        };
+
+        gcode.push(Command::Comment(format!("tool: {}", job_config.tool)));
+        gcode.push(power_or_rpm_on);
+
+        let end_mill_diameter = match tool_selection {
+            ToolSelection::Spindle {
+                bit: SpindleBit::EndMill { diameter },
+                ..
+            } => Some(diameter.get::<millimeter>()),
+            _ => None,
+        };
+
+        for trace in &self.traces {
+            let offsets: Vec<f32> = match end_mill_diameter {
+                Some(tool_diameter) => {
+                    let tool_radius = tool_diameter / 2.0;
+                    let first_pass = trace.width / 2.0 + tool_radius;
+                    let step = tool_diameter * (1.0 - job_config.overlap);
+                    (0..job_config.isolation_passes.max(1))
+                        .map(|pass| first_pass + pass as f32 * step)
+                        .collect()
+                }
+                // No offsetting for a laser: the beam already traces the net's edge.
+                None => vec![0.0],
+            };
+
+            for offset in offsets {
+                for (start, end) in offset_trace(trace, offset) {
+                    if travel_between_traces {
+                        gcode.push(Command::RapidMove {
+                            x: start.x,
+                            y: start.y,
+                            z: safe_z,
+                        });
+                    }
+
+                    gcode.push(Command::LinearMove {
+                        x: start.x,
+                        y: start.y,
+                        z: 0.0,
+                        feed_rate: work_speed,
+                    });
+                    gcode.push(Command::LinearMove {
+                        x: end.x,
+                        y: end.y,
+                        z: 0.0,
+                        feed_rate: work_speed,
+                    });
+                }
+            }
+        }
+
+        gcode.push(Command::ToolOff);
+
+        Ok(())
+    }
+
+    /// Generates a multi-pass board-outline cut, stepping down by the end mill's `max_cut_depth`
+    /// each lap until `stock_thickness` is reached, and leaving `tab_count` holding tabs of
+    /// `tab_width` x `tab_height` evenly spaced around each closed contour.
+    pub fn generate_cut_gcode(
+        &self,
+        gcode: &mut Vec<Command>,
+        job_config: &JobConfig,
+        tool_selection: &ToolSelection<'_>,
+        tab_count: u32,
+        tab_width: f32,
+        tab_height: f32,
+    ) -> Result<()> {
+        let safe_z = 5.0;
+
+        let ToolSelection::Spindle { spindle, bit } = tool_selection else {
+            bail!("Board cutting requires a spindle tool; lasers cannot cut through stock.");
+        };
+
+        let SpindleBit::EndMill { diameter } = bit else {
+            bail!("Board cutting requires an end mill bit to be selected.");
+        };
+
+        let ToolConfig::EndMill {
+            spindle_rpm,
+            max_cut_depth,
+            plunge_speed,
+            work_speed,
+        } = &job_config.tool_power
+        else {
+            bail!("Board cutting requires an end-mill job config (spindle RPM, max cut depth, feeds).");
+        };
+
+        let tool_radius = diameter.get::<millimeter>() / 2.0;
+        let max_cut_depth = max_cut_depth.get::<millimeter>();
+        let stock_thickness = job_config.stock_thickness.get::<millimeter>();
+        let work_speed = work_speed.get::<uom::si::velocity::millimeter_per_minute>();
+
+        if max_cut_depth <= 0.0 {
+            bail!("End mill max_cut_depth must be positive, got {max_cut_depth}mm.");
+        }
+
+        let contours = self.closed_contours();
+        if contours.is_empty() {
+            bail!("No closed contours found in the board-outline layer.");
+        }
+
+        gcode.push(Command::Comment(format!("tool: {}", job_config.tool)));
+        gcode.push(Command::SpindleOn {
+            rpm: spindle_rpm.get::<revolution_per_minute>(),
+        });
+
+        let mut passes = Vec::new();
+        let mut depth = max_cut_depth;
+        loop {
+            let clamped = depth.min(stock_thickness);
+            passes.push(clamped);
+            if clamped >= stock_thickness {
+                break;
+            }
+            depth += max_cut_depth;
+        }
+
+        for outline in &contours {
+            let offset = outline.offset(tool_radius);
+            let tabs = offset.evenly_spaced_segments(tab_count, tab_width);
+
+            // A tab almost never lands exactly on a pre-existing polygon vertex (it's placed by
+            // arc length, not by corner), so split every edge at the tab boundaries that fall
+            // inside it. Without this, the Z transition into/out of a tab is only ever tested at
+            // the original corners and silently never triggers for a typical rectangular outline.
+            let subdivided_edges: Vec<(Vector2<f32>, Vector2<f32>)> = offset
+                .edges()
+                .flat_map(|(start, end)| {
+                    let points = subdivide_edge_at_tabs(start, end, &tabs);
+                    (0..points.len() - 1)
+                        .map(move |index| (points[index], points[index + 1]))
+                        .collect::<Vec<_>>()
+                })
+                .collect();
+
+            for &depth in &passes {
+                let tab_retained_depth = (stock_thickness - tab_height).max(0.0);
+                let effective_depth = if depth > tab_retained_depth {
+                    tab_retained_depth
+                } else {
+                    depth
+                };
+
+                gcode.push(Command::RapidMove {
+                    x: offset.points[0].x,
+                    y: offset.points[0].y,
+                    z: safe_z,
+                });
+                gcode.push(Command::LinearMove {
+                    x: offset.points[0].x,
+                    y: offset.points[0].y,
+                    z: -depth,
+                    feed_rate: plunge_speed.get::<uom::si::velocity::millimeter_per_minute>(),
+                });
+
+                for &(start, end) in &subdivided_edges {
+                    let in_tab = tab_count > 0
+                        && depth > tab_retained_depth
+                        && tabs.iter().any(|(tab_start, tab_end)| {
+                            point_on_segment(start, *tab_start, *tab_end)
+                                && point_on_segment(end, *tab_start, *tab_end)
+                        });
+
+                    let cut_z = if in_tab { -effective_depth } else { -depth };
+
+                    gcode.push(Command::LinearMove {
+                        x: end.x,
+                        y: end.y,
+                        z: cut_z,
+                        feed_rate: work_speed,
+                    });
+                }
+            }
+        }
+
+        gcode.push(Command::RapidMove {
+            x: 0.0,
+            y: 0.0,
+            z: safe_z,
+        });
+        gcode.push(Command::ToolOff);
+
+        Ok(())
+    }
+}
+
+/// Returns the one or two line segments `distance` away from `trace`'s centerline.
+///
+/// A `distance` of `0.0` returns just the centerline itself; any other distance returns both the
+/// left- and right-hand parallel offsets, since an isolation pass has to clear copper on both
+/// sides of the trace.
+fn offset_trace(trace: &Trace, distance: f32) -> Vec<(Vector2<f32>, Vector2<f32>)> {
+    if distance == 0.0 {
+        return vec![(trace.start, trace.end)];
+    }
+
+    let direction = (trace.end - trace.start).normalize();
+    let normal = Vector2::new(-direction.y, direction.x);
+
+    vec![
+        (trace.start + normal * distance, trace.end + normal * distance),
+        (trace.start - normal * distance, trace.end - normal * distance),
+    ]
+}
+
+/// Splits the edge `(start, end)` at every tab boundary point from `tabs` that falls strictly
+/// inside it, returning the resulting chain of vertices (always starting with `start` and ending
+/// with `end`). This lets a holding tab's Z transition land on its own vertex instead of only
+/// ever being tested against the polygon's pre-existing corners.
+fn subdivide_edge_at_tabs(
+    start: Vector2<f32>,
+    end: Vector2<f32>,
+    tabs: &[(Vector2<f32>, Vector2<f32>)],
+) -> Vec<Vector2<f32>> {
+    let edge = end - start;
+    let length_squared = edge.norm_squared();
+
+    let mut splits: Vec<(f32, Vector2<f32>)> = Vec::new();
+    if length_squared > f32::EPSILON {
+        for &(tab_start, tab_end) in tabs {
+            for point in [tab_start, tab_end] {
+                let t = (point - start).dot(&edge) / length_squared;
+                if t > 1e-3 && t < 1.0 - 1e-3 && point_on_segment(point, start, end) {
+                    splits.push((t, point));
+                }
+            }
+        }
+    }
+
+    splits.sort_by(|a, b| a.0.partial_cmp(&b.0).unwrap());
+    splits.dedup_by(|a, b| (a.0 - b.0).abs() < 1e-4);
+
+    let mut points = vec![start];
+    points.extend(splits.into_iter().map(|(_, point)| point));
+    points.push(end);
+    points
+}
+
+/// Whether `point` lies on the segment `(start, end)`, within a small tolerance. Used to decide
+/// whether a contour edge falls inside a holding-tab gap.
+fn point_on_segment(point: Vector2<f32>, start: Vector2<f32>, end: Vector2<f32>) -> bool {
+    const EPSILON: f32 = 1e-3;
+    let segment = end - start;
+    let to_point = point - start;
+    let length_squared = segment.norm_squared();
+    if length_squared < EPSILON {
+        return (to_point).norm() < EPSILON;
+    }
+
+    let t = (to_point.dot(&segment) / length_squared).clamp(0.0, 1.0);
+    let closest = start + segment * t;
+    (point - closest).norm() < EPSILON
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use uom::si::{
+        angular_velocity::{revolution_per_minute, AngularVelocity},
+        length::Length,
+        velocity::Velocity,
+    };
+
+    use crate::config::machine::SpindleConfig;
+
+    use super::*;
+
+    fn square_traces() -> Vec<Trace> {
+        vec![
+            Trace { start: Vector2::new(0.0, 0.0), end: Vector2::new(10.0, 0.0), width: 0.2 },
+            Trace { start: Vector2::new(10.0, 0.0), end: Vector2::new(10.0, 10.0), width: 0.2 },
+            Trace { start: Vector2::new(10.0, 10.0), end: Vector2::new(0.0, 10.0), width: 0.2 },
+            Trace { start: Vector2::new(0.0, 10.0), end: Vector2::new(0.0, 0.0), width: 0.2 },
+        ]
+    }
+
+    #[test]
+    fn closed_contours_stitches_disjoint_traces_into_a_polygon() {
+        let gerber = GerberFile { traces: square_traces() };
+        let contours = gerber.closed_contours();
+
+        assert_eq!(contours.len(), 1);
+        assert_eq!(contours[0].points.len(), 4);
+    }
+
+    #[test]
+    fn closed_contours_ignores_traces_that_never_close() {
+        let gerber = GerberFile {
+            traces: vec![Trace { start: Vector2::new(0.0, 0.0), end: Vector2::new(10.0, 0.0), width: 0.2 }],
+        };
+
+        assert!(gerber.closed_contours().is_empty());
+    }
+
+    #[test]
+    fn point_on_segment_true_for_midpoint_false_off_axis() {
+        let start = Vector2::new(0.0, 0.0);
+        let end = Vector2::new(10.0, 0.0);
+
+        assert!(point_on_segment(Vector2::new(5.0, 0.0), start, end));
+        assert!(!point_on_segment(Vector2::new(5.0, 1.0), start, end));
+        assert!(!point_on_segment(Vector2::new(15.0, 0.0), start, end));
+    }
+
+    #[test]
+    fn subdivide_edge_at_tabs_splits_at_interior_tab_boundaries() {
+        let start = Vector2::new(0.0, 0.0);
+        let end = Vector2::new(10.0, 0.0);
+        let tabs = vec![(Vector2::new(4.0, 0.0), Vector2::new(6.0, 0.0))];
+
+        let points = subdivide_edge_at_tabs(start, end, &tabs);
+
+        assert_eq!(points, vec![start, Vector2::new(4.0, 0.0), Vector2::new(6.0, 0.0), end]);
+    }
+
+    #[test]
+    fn subdivide_edge_at_tabs_is_a_no_op_when_no_tab_falls_inside() {
+        let start = Vector2::new(0.0, 0.0);
+        let end = Vector2::new(10.0, 0.0);
+        let tabs = vec![(Vector2::new(20.0, 0.0), Vector2::new(22.0, 0.0))];
+
+        assert_eq!(subdivide_edge_at_tabs(start, end, &tabs), vec![start, end]);
+    }
+
+    #[test]
+    fn offset_trace_zero_distance_returns_centerline_only() {
+        let trace = Trace { start: Vector2::new(0.0, 0.0), end: Vector2::new(10.0, 0.0), width: 0.2 };
+        assert_eq!(offset_trace(&trace, 0.0), vec![(trace.start, trace.end)]);
+    }
+
+    #[test]
+    fn offset_trace_nonzero_distance_returns_both_parallel_offsets() {
+        let trace = Trace { start: Vector2::new(0.0, 0.0), end: Vector2::new(10.0, 0.0), width: 0.2 };
+        let offsets = offset_trace(&trace, 1.0);
+
+        assert_eq!(offsets.len(), 2);
+        assert!(offsets
+            .iter()
+            .any(|(s, e)| (s.y - 1.0).abs() < 1e-6 && (e.y - 1.0).abs() < 1e-6));
+        assert!(offsets
+            .iter()
+            .any(|(s, e)| (s.y + 1.0).abs() < 1e-6 && (e.y + 1.0).abs() < 1e-6));
+    }
+
+    fn end_mill_job_config(isolation_passes: u32, overlap: f32) -> JobConfig {
+        JobConfig {
+            tool: "end_mill".into(),
+            distance_per_step: Length::new::<millimeter>(0.1),
+            stock_thickness: Length::new::<millimeter>(1.6),
+            peck_depth: Length::new::<millimeter>(0.5),
+            drill_tolerance: Length::new::<millimeter>(0.1),
+            isolation_passes,
+            overlap,
+            tool_power: ToolConfig::EndMill {
+                spindle_rpm: AngularVelocity::new::<revolution_per_minute>(10_000.0),
+                max_cut_depth: Length::new::<millimeter>(0.5),
+                plunge_speed: Velocity::new::<uom::si::velocity::millimeter_per_minute>(100.0),
+                work_speed: Velocity::new::<uom::si::velocity::millimeter_per_minute>(500.0),
+            },
+        }
+    }
+
+    #[test]
+    fn generate_gcode_widens_gap_by_requested_isolation_passes() {
+        let tool_diameter = 1.0;
+        let trace_width = 0.2;
+        let spindle = SpindleConfig {
+            max_speed: AngularVelocity::new::<revolution_per_minute>(10_000.0),
+            bits: HashMap::new(),
+        };
+        let bit = SpindleBit::EndMill { diameter: Length::new::<millimeter>(tool_diameter) };
+        let tool_selection = ToolSelection::Spindle { spindle: &spindle, bit: &bit };
+
+        let trace = Trace { start: Vector2::new(0.0, 0.0), end: Vector2::new(10.0, 0.0), width: trace_width };
+        let gerber = GerberFile { traces: vec![trace] };
+        let job_config = end_mill_job_config(3, 0.3);
+
+        let mut gcode = Vec::new();
+        gerber.generate_gcode(&mut gcode, &job_config, &tool_selection, false).unwrap();
+
+        let offsets: Vec<f32> = gcode
+            .iter()
+            .filter_map(|command| match command {
+                Command::LinearMove { x, y, .. } if *y >= 0.0 && *x == 0.0 => Some(*y),
+                _ => None,
+            })
+            .collect();
+
+        // 3 passes, each moved outward by tool_diameter * (1 - overlap) from the first pass.
+        let step = tool_diameter * (1.0 - 0.3);
+        let first_pass = trace_width / 2.0 + tool_diameter / 2.0;
+        assert_eq!(offsets.len(), 3);
+        for (pass, offset) in offsets.iter().enumerate() {
+            assert!((offset - (first_pass + pass as f32 * step)).abs() < 1e-5);
+        }
+    }
+}
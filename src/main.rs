@@ -6,15 +6,18 @@ mod arguments;
 mod config;
 use config::Config;
 use uom::si::length::{millimeter, Length};
+mod excellon;
 mod gcode_generation;
 mod geometry;
 mod gerber_file;
 mod parsing;
+mod post_process;
+mod workspace_fit;
 
 use crate::{
-    config::machine::Tool,
+    config::machine::{Machine, Tool, ToolConfig, WorkspaceSize},
     forge_file::ForgeFile,
-    gcode_generation::{GCodeFile, ToolSelection},
+    gcode_generation::{GCodeFile, GCodeFlavor, ToolSelection},
     gerber_file::GerberFile,
 };
 mod forge_file;
@@ -80,9 +83,9 @@ fn build(build_configuration: arguments::BuildCommand, global_config: Config) ->
                 machine_config,
                 gerber_file,
                 gcode_file,
+                origin,
+                auto_fit,
             } => {
-                let gcode = gcode_files.entry(gcode_file.clone()).or_default();
-
                 log::info!("Process engrave stage: {:?}", gerber_file);
                 let machine_config_path = machine_config
                     .as_ref()
@@ -147,6 +150,16 @@ fn build(build_configuration: arguments::BuildCommand, global_config: Config) ->
                     }
                 };
 
+                let gcode = &mut gcode_file_context(
+                    &mut gcode_files,
+                    gcode_file,
+                    machine_config,
+                    &tool_name,
+                    &job_config.tool_power,
+                )?
+                .commands;
+                let commands_start = gcode.len();
+
                 let file_path = build_configuration
                     .forge_file_path
                     .parent()
@@ -211,21 +224,257 @@ fn build(build_configuration: arguments::BuildCommand, global_config: Config) ->
                 gerber
                     .generate_gcode(gcode, job_config, &tool_selection, true)
                     .context("Failed to generate GCode file.")?;
+
+                workspace_fit::fit_to_workspace(
+                    &mut gcode[commands_start..],
+                    machine_config.workspace_area,
+                    *origin,
+                    *auto_fit,
+                    &format!("Stage {stage_index} (engrave_mask, {gerber_file})"),
+                )?;
             }
             forge_file::Stage::CutBoard {
                 machine_config,
                 gcode_file,
                 file,
+                tab_count,
+                tab_width,
+                tab_height,
+                origin,
+                auto_fit,
             } => {
-                // TODO
                 log::info!("Process cutting stage: {}", file);
+                let machine_config_path = machine_config
+                    .as_ref()
+                    .or(global_config.default_cutter.as_ref())
+                    .context("A cutter was not specified and a global default is not set.")?;
+                log::info!("Using machine configuration: {}", machine_config_path);
+
+                let mut machine_config_path = machine_config_path.iter();
+                let machine_name = machine_config_path
+                    .next()
+                    .context("Machine name not provided by machine config path.")?
+                    .to_string();
+                let machine_profile = machine_config_path
+                    .next()
+                    .context("Machine profile not provided by machine config path.")?
+                    .to_string();
+
+                if machine_config_path.next().is_some() {
+                    bail!("Too many parts to machine config path.");
+                }
+
+                let machine_config = forge_file
+                    .machines
+                    .get(&machine_name)
+                    .or(global_config.machines.get(&machine_name))
+                    .context("Failed to find machine configuration.")?;
+
+                let job_config = machine_config
+                    .cutting_configs
+                    .get(&machine_profile)
+                    .context("Failed to find machine profile.")?;
+
+                log::info!("Tool Info: {}", job_config.tool_power);
+
+                let mut tool_path = job_config.tool.ancestors();
+                let tool_name = tool_path
+                    .next()
+                    .context("no tool name provided")?
+                    .to_string();
+
+                log::info!("Using tool: {}", tool_name);
+
+                let tool = machine_config
+                    .tools
+                    .get(&tool_name)
+                    .context("Could not find specified tool.")?;
+
+                let bit_name = tool_path.next().map(|name| name.to_string());
+
+                let tool_selection = match tool {
+                    Tool::Laser(laser) => ToolSelection::Laser { laser },
+                    Tool::Spindle(spindle) => {
+                        let bit_name = bit_name.context("No bit name provided for spindle.")?;
+                        log::info!("Using bit: {}", bit_name);
+                        ToolSelection::Spindle {
+                            spindle,
+                            bit: spindle
+                                .bits
+                                .get(&bit_name)
+                                .context("Spindle does not have a bit with requested name.")?,
+                        }
+                    }
+                };
+
+                let gcode = &mut gcode_file_context(
+                    &mut gcode_files,
+                    gcode_file,
+                    machine_config,
+                    &tool_name,
+                    &job_config.tool_power,
+                )?
+                .commands;
+                let commands_start = gcode.len();
+
+                let file_path = build_configuration
+                    .forge_file_path
+                    .parent()
+                    .context("Could not get working directory of forge file.")?
+                    .join(file);
+
+                let mut gerber = GerberFile::default();
+                gerber_file::load(&mut gerber, &file_path)
+                    .context("Failed to load board outline gerber file.")?;
+
+                gerber
+                    .generate_cut_gcode(
+                        gcode,
+                        job_config,
+                        &tool_selection,
+                        *tab_count,
+                        tab_width.get::<millimeter>(),
+                        tab_height.get::<millimeter>(),
+                    )
+                    .context("Failed to generate board cutting GCode.")?;
+
+                workspace_fit::fit_to_workspace(
+                    &mut gcode[commands_start..],
+                    machine_config.workspace_area,
+                    *origin,
+                    *auto_fit,
+                    &format!("Stage {stage_index} (cut_board, {file})"),
+                )?;
+            }
+            forge_file::Stage::DrillHoles {
+                machine_config,
+                gcode_file,
+                file,
+                origin,
+                auto_fit,
+            } => {
+                log::info!("Process drill stage: {}", file);
+                let machine_config_path = machine_config
+                    .as_ref()
+                    .or(global_config.default_drill.as_ref())
+                    .context("A drill profile was not specified and a global default is not set.")?;
+                log::info!("Using machine configuration: {}", machine_config_path);
+
+                let mut machine_config_path = machine_config_path.iter();
+                let machine_name = machine_config_path
+                    .next()
+                    .context("Machine name not provided by machine config path.")?
+                    .to_string();
+                let machine_profile = machine_config_path
+                    .next()
+                    .context("Machine profile not provided by machine config path.")?
+                    .to_string();
+
+                if machine_config_path.next().is_some() {
+                    bail!("Too many parts to machine config path.");
+                }
+
+                let machine_config = forge_file
+                    .machines
+                    .get(&machine_name)
+                    .or(global_config.machines.get(&machine_name))
+                    .context("Failed to find machine configuration.")?;
+
+                let job_config = machine_config
+                    .drilling_configs
+                    .get(&machine_profile)
+                    .context("Failed to find machine profile.")?;
+
+                log::info!("Tool Info: {}", job_config.tool_power);
+
+                let mut tool_path = job_config.tool.ancestors();
+                let tool_name = tool_path
+                    .next()
+                    .context("no tool name provided")?
+                    .to_string();
+
+                log::info!("Using tool: {}", tool_name);
+
+                let tool = machine_config
+                    .tools
+                    .get(&tool_name)
+                    .context("Could not find specified tool.")?;
+
+                let bit_name = tool_path.next().map(|name| name.to_string());
+
+                let tool_selection = match tool {
+                    Tool::Laser(laser) => ToolSelection::Laser { laser },
+                    Tool::Spindle(spindle) => {
+                        let bit_name = bit_name.context("No bit name provided for spindle.")?;
+                        log::info!("Using bit: {}", bit_name);
+                        ToolSelection::Spindle {
+                            spindle,
+                            bit: spindle
+                                .bits
+                                .get(&bit_name)
+                                .context("Spindle does not have a bit with requested name.")?,
+                        }
+                    }
+                };
+
+                let gcode = &mut gcode_file_context(
+                    &mut gcode_files,
+                    gcode_file,
+                    machine_config,
+                    &tool_name,
+                    &job_config.tool_power,
+                )?
+                .commands;
+                let commands_start = gcode.len();
+
+                let file_path = build_configuration
+                    .forge_file_path
+                    .parent()
+                    .context("Could not get working directory of forge file.")?
+                    .join(file);
+
+                let drill_file = excellon::load(&file_path).context("Failed to load drill file.")?;
+
+                excellon::generate_drill_gcode(&drill_file, gcode, job_config, &tool_selection)
+                    .context("Failed to generate drilling GCode.")?;
+
+                workspace_fit::fit_to_workspace(
+                    &mut gcode[commands_start..],
+                    machine_config.workspace_area,
+                    *origin,
+                    *auto_fit,
+                    &format!("Stage {stage_index} (drill_holes, {file})"),
+                )?;
             }
         }
     }
 
-    for (path, commands) in gcode_files {
+    for (path, context) in gcode_files {
+        let GCodeFileContext {
+            flavor,
+            commands,
+            post_process_script,
+            tool_name,
+            tool_power,
+            workspace,
+        } = context;
+
+        let commands = match post_process_script {
+            Some(script_path) => post_process::run(
+                &script_path,
+                commands,
+                &post_process::PostProcessContext {
+                    tool_name: &tool_name,
+                    workspace,
+                    tool_power: &tool_power,
+                },
+            )
+            .with_context(|| format!("Failed to post-process GCode for file: {:?}", path))?,
+            None => commands,
+        };
+
         let output_file = build_configuration.target_directory.join(&path);
-        let gcode_file = GCodeFile::new(commands);
+        let gcode_file = GCodeFile::new(commands, flavor);
         let output = gcode_file
             .to_string()
             .with_context(|| format!("Failed to produce GCode for file: {:?}", path))?;
@@ -234,3 +483,54 @@ fn build(build_configuration: arguments::BuildCommand, global_config: Config) ->
 
     Ok(())
 }
+
+/// The accumulated state for a single output `.gcode` file, built up as forge stages write into
+/// it and finally consumed when the file is serialized. `tool_name`/`tool_power` reflect whichever
+/// stage last wrote to the file, since that's the tool a post-process script would want to know
+/// about when rewriting the tail end of a shared file.
+struct GCodeFileContext {
+    flavor: GCodeFlavor,
+    commands: Vec<gcode_generation::Command>,
+    post_process_script: Option<camino::Utf8PathBuf>,
+    tool_name: String,
+    tool_power: ToolConfig,
+    workspace: WorkspaceSize,
+}
+
+/// Returns the mutable context for `gcode_file`, creating it from `machine_config` on first use
+/// and bailing if a later stage tries to write the same output file with a differently-flavored
+/// machine. `post_process_script`, `tool_name`, `tool_power`, and `workspace` are overwritten on
+/// every call, so they always reflect whichever stage last wrote to this file.
+fn gcode_file_context<'a>(
+    gcode_files: &'a mut HashMap<camino::Utf8PathBuf, GCodeFileContext>,
+    gcode_file: &camino::Utf8PathBuf,
+    machine_config: &Machine,
+    tool_name: &str,
+    tool_power: &ToolConfig,
+) -> Result<&'a mut GCodeFileContext> {
+    let entry = gcode_files
+        .entry(gcode_file.clone())
+        .or_insert_with(|| GCodeFileContext {
+            flavor: machine_config.flavor,
+            commands: Vec::new(),
+            post_process_script: machine_config.post_process_script.clone(),
+            tool_name: tool_name.to_string(),
+            tool_power: tool_power.clone(),
+            workspace: machine_config.workspace_area,
+        });
+
+    if entry.flavor != machine_config.flavor {
+        bail!(
+            "Stages writing to {gcode_file} disagree on G-code flavor ({:?} vs {:?}); split them into separate files.",
+            entry.flavor,
+            machine_config.flavor
+        );
+    }
+
+    entry.tool_name = tool_name.to_string();
+    entry.tool_power = tool_power.clone();
+    entry.post_process_script = machine_config.post_process_script.clone();
+    entry.workspace = machine_config.workspace_area;
+
+    Ok(entry)
+}
@@ -0,0 +1,81 @@
+use serde::{de::Error as _, Deserialize, Deserializer};
+use uom::si::{
+    angular_velocity::{radian_per_second, revolution_per_minute, AngularVelocity},
+    length::{centimeter, inch, meter, millimeter, Length},
+    power::{kilowatt, watt, Power},
+    velocity::{centimeter_per_minute, inch_per_minute, millimeter_per_minute, Velocity},
+};
+
+/// Implemented by every `uom` quantity type used in config files, letting [`parse_quantity`]
+/// stay generic while each dimension keeps its own table of recognized unit suffixes.
+pub trait ParsableQuantity: Sized {
+    /// Builds the quantity from a bare numeric value and the unit suffix that followed it
+    /// (e.g. `"mm"`, `"W"`, `"rpm"`). Returns `None` if the suffix is not recognized.
+    fn from_value_and_unit(value: f32, unit: &str) -> Option<Self>;
+}
+
+impl ParsableQuantity for Length<uom::si::SI<f32>, f32> {
+    fn from_value_and_unit(value: f32, unit: &str) -> Option<Self> {
+        Some(match unit {
+            "mm" => Length::new::<millimeter>(value),
+            "cm" => Length::new::<centimeter>(value),
+            "m" => Length::new::<meter>(value),
+            "in" | "\"" => Length::new::<inch>(value),
+            _ => return None,
+        })
+    }
+}
+
+impl ParsableQuantity for Power<uom::si::SI<f32>, f32> {
+    fn from_value_and_unit(value: f32, unit: &str) -> Option<Self> {
+        Some(match unit {
+            "W" => Power::new::<watt>(value),
+            "kW" => Power::new::<kilowatt>(value),
+            _ => return None,
+        })
+    }
+}
+
+impl ParsableQuantity for AngularVelocity<uom::si::SI<f32>, f32> {
+    fn from_value_and_unit(value: f32, unit: &str) -> Option<Self> {
+        Some(match unit {
+            "rpm" => AngularVelocity::new::<revolution_per_minute>(value),
+            "rad/s" => AngularVelocity::new::<radian_per_second>(value),
+            _ => return None,
+        })
+    }
+}
+
+impl ParsableQuantity for Velocity<uom::si::SI<f32>, f32> {
+    fn from_value_and_unit(value: f32, unit: &str) -> Option<Self> {
+        Some(match unit {
+            "mm/min" => Velocity::new::<millimeter_per_minute>(value),
+            "cm/min" => Velocity::new::<centimeter_per_minute>(value),
+            "in/min" => Velocity::new::<inch_per_minute>(value),
+            _ => return None,
+        })
+    }
+}
+
+/// Splits a config value such as `"1.6mm"` into its numeric and unit-suffix parts.
+fn split_value_and_unit(raw: &str) -> Option<(f32, &str)> {
+    let raw = raw.trim();
+    let split_at = raw.find(|c: char| !(c.is_ascii_digit() || c == '.' || c == '-' || c == '+'))?;
+    let (value, unit) = raw.split_at(split_at);
+    Some((value.trim().parse().ok()?, unit.trim()))
+}
+
+/// Deserializes a `uom` quantity written as a number followed by a unit suffix, e.g.
+/// `"1.6mm"`, `"40W"` or `"10000rpm"`. Used throughout the machine config so that units are
+/// explicit and self-documenting on disk.
+pub fn parse_quantity<'de, D, Q>(deserializer: D) -> Result<Q, D::Error>
+where
+    D: Deserializer<'de>,
+    Q: ParsableQuantity,
+{
+    let raw = String::deserialize(deserializer)?;
+    let (value, unit) = split_value_and_unit(&raw)
+        .ok_or_else(|| D::Error::custom(format!("Could not parse quantity from {:?}.", raw)))?;
+    Q::from_value_and_unit(value, unit)
+        .ok_or_else(|| D::Error::custom(format!("Unrecognized unit {:?} in {:?}.", unit, raw)))
+}
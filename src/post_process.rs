@@ -0,0 +1,208 @@
+use anyhow::{bail, Context, Result};
+use camino::Utf8Path;
+use mlua::{Lua, Table};
+use uom::si::{
+    angular_velocity::revolution_per_minute, length::millimeter, power::watt,
+    velocity::millimeter_per_minute,
+};
+
+use crate::{
+    config::machine::{ToolConfig, WorkspaceSize},
+    gcode_generation::Command,
+};
+
+/// The read-only information made available to a post-processing script, in addition to the
+/// mutable `commands` list.
+pub struct PostProcessContext<'a> {
+    pub tool_name: &'a str,
+    pub workspace: WorkspaceSize,
+    pub tool_power: &'a ToolConfig,
+}
+
+/// Runs `script_path` against `commands`, returning the (possibly rewritten) command stream the
+/// script left behind.
+///
+/// The script sees a global `commands` array it can freely read, insert into, delete from, or
+/// overwrite, plus read-only globals describing the active tool and workspace. Whatever
+/// `commands` looks like when the script finishes is what gets serialized to G-code.
+pub fn run(
+    script_path: &Utf8Path,
+    commands: Vec<Command>,
+    context: &PostProcessContext<'_>,
+) -> Result<Vec<Command>> {
+    let source = std::fs::read_to_string(script_path)
+        .with_context(|| format!("Failed to read post-process script {script_path}."))?;
+
+    let lua = Lua::new();
+    let globals = lua.globals();
+
+    globals
+        .set("tool_name", context.tool_name)
+        .context("Failed to expose tool_name to the post-process script.")?;
+    globals
+        .set("workspace_width_mm", context.workspace.width.get::<millimeter>())
+        .context("Failed to expose workspace size to the post-process script.")?;
+    globals
+        .set("workspace_height_mm", context.workspace.height.get::<millimeter>())
+        .context("Failed to expose workspace size to the post-process script.")?;
+    globals
+        .set("tool", tool_config_to_table(&lua, context.tool_power)?)
+        .context("Failed to expose the active tool config to the post-process script.")?;
+
+    let commands_table = lua.create_table().context("Failed to create commands table.")?;
+    for (index, command) in commands.iter().enumerate() {
+        commands_table
+            .set(index + 1, command_to_table(&lua, command)?)
+            .context("Failed to populate commands table.")?;
+    }
+    globals
+        .set("commands", commands_table)
+        .context("Failed to expose commands to the post-process script.")?;
+
+    lua.load(&source)
+        .set_name(script_path.as_str())
+        .exec()
+        .with_context(|| format!("Post-process script {script_path} failed."))?;
+
+    let commands_table: Table<'_> = globals
+        .get("commands")
+        .context("Post-process script removed the global `commands` table.")?;
+
+    commands_table
+        .sequence_values::<Table<'_>>()
+        .map(|entry| table_to_command(entry.context("Malformed entry in `commands` table.")?))
+        .collect()
+}
+
+fn tool_config_to_table<'lua>(lua: &'lua Lua, tool_power: &ToolConfig) -> Result<Table<'lua>> {
+    let table = lua.create_table().context("Failed to create tool table.")?;
+
+    match tool_power {
+        ToolConfig::Laser { laser_power, work_speed } => {
+            table.set("kind", "laser")?;
+            table.set("laser_power_w", laser_power.get::<watt>())?;
+            table.set("work_speed_mm_min", work_speed.get::<millimeter_per_minute>())?;
+        }
+        ToolConfig::Drill { spindle_rpm, plunge_speed } => {
+            table.set("kind", "drill")?;
+            table.set("spindle_rpm", spindle_rpm.get::<revolution_per_minute>())?;
+            table.set("plunge_speed_mm_min", plunge_speed.get::<millimeter_per_minute>())?;
+        }
+        ToolConfig::EndMill {
+            spindle_rpm,
+            max_cut_depth,
+            plunge_speed,
+            work_speed,
+        } => {
+            table.set("kind", "end_mill")?;
+            table.set("spindle_rpm", spindle_rpm.get::<revolution_per_minute>())?;
+            table.set("max_cut_depth_mm", max_cut_depth.get::<millimeter>())?;
+            table.set("plunge_speed_mm_min", plunge_speed.get::<millimeter_per_minute>())?;
+            table.set("work_speed_mm_min", work_speed.get::<millimeter_per_minute>())?;
+        }
+    }
+
+    Ok(table)
+}
+
+fn command_to_table<'lua>(lua: &'lua Lua, command: &Command) -> Result<Table<'lua>> {
+    let table = lua.create_table().context("Failed to create command table.")?;
+
+    match command {
+        Command::RapidMove { x, y, z } => {
+            table.set("type", "RapidMove")?;
+            table.set("x", *x)?;
+            table.set("y", *y)?;
+            table.set("z", *z)?;
+        }
+        Command::LinearMove { x, y, z, feed_rate } => {
+            table.set("type", "LinearMove")?;
+            table.set("x", *x)?;
+            table.set("y", *y)?;
+            table.set("z", *z)?;
+            table.set("feed_rate", *feed_rate)?;
+        }
+        Command::Arc {
+            x,
+            y,
+            z,
+            center_x,
+            center_y,
+            clockwise,
+            feed_rate,
+        } => {
+            table.set("type", "Arc")?;
+            table.set("x", *x)?;
+            table.set("y", *y)?;
+            table.set("z", *z)?;
+            table.set("center_x", *center_x)?;
+            table.set("center_y", *center_y)?;
+            table.set("clockwise", *clockwise)?;
+            table.set("feed_rate", *feed_rate)?;
+        }
+        Command::LaserOn { power } => {
+            table.set("type", "LaserOn")?;
+            table.set("power", *power)?;
+        }
+        Command::SpindleOn { rpm } => {
+            table.set("type", "SpindleOn")?;
+            table.set("rpm", *rpm)?;
+        }
+        Command::ToolOff => table.set("type", "ToolOff")?,
+        Command::Dwell { seconds } => {
+            table.set("type", "Dwell")?;
+            table.set("seconds", *seconds)?;
+        }
+        Command::Comment(text) => {
+            table.set("type", "Comment")?;
+            table.set("text", text.as_str())?;
+        }
+    }
+
+    Ok(table)
+}
+
+fn table_to_command(table: Table<'_>) -> Result<Command> {
+    let kind: String = table.get("type").context("Command table is missing a `type` field.")?;
+
+    let get = |field: &'static str| -> Result<f32> {
+        table
+            .get(field)
+            .with_context(|| format!("{kind} command is missing field `{field}`."))
+    };
+
+    Ok(match kind.as_str() {
+        "RapidMove" => Command::RapidMove {
+            x: get("x")?,
+            y: get("y")?,
+            z: get("z")?,
+        },
+        "LinearMove" => Command::LinearMove {
+            x: get("x")?,
+            y: get("y")?,
+            z: get("z")?,
+            feed_rate: get("feed_rate")?,
+        },
+        "Arc" => Command::Arc {
+            x: get("x")?,
+            y: get("y")?,
+            z: get("z")?,
+            center_x: get("center_x")?,
+            center_y: get("center_y")?,
+            clockwise: table
+                .get("clockwise")
+                .context("Arc command is missing field `clockwise`.")?,
+            feed_rate: get("feed_rate")?,
+        },
+        "LaserOn" => Command::LaserOn { power: get("power")? },
+        "SpindleOn" => Command::SpindleOn { rpm: get("rpm")? },
+        "ToolOff" => Command::ToolOff,
+        "Dwell" => Command::Dwell { seconds: get("seconds")? },
+        "Comment" => Command::Comment(
+            table
+                .get("text")
+                .context("Comment command is missing field `text`.")?,
+        ),
+        other => bail!("Unknown command type {other:?} produced by post-process script."),
+    })
+}
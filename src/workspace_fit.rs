@@ -0,0 +1,187 @@
+use anyhow::{bail, Result};
+use nalgebra::Vector2;
+use uom::si::length::millimeter;
+
+use crate::{config::machine::WorkspaceSize, forge_file::Origin, gcode_generation::Command};
+
+/// The axis-aligned extent of a set of motion commands, in machine coordinates (millimeters).
+#[derive(Debug, Clone, Copy)]
+struct BoundingBox {
+    min: Vector2<f32>,
+    max: Vector2<f32>,
+}
+
+impl BoundingBox {
+    /// Computes the bounding box of every `RapidMove`/`LinearMove`/`Arc` endpoint in `commands`,
+    /// or `None` if none of them move the tool. Arc travel between endpoints is not accounted
+    /// for, so a very bulgy arc could extend slightly outside the reported box.
+    fn of(commands: &[Command]) -> Option<Self> {
+        let mut points = commands.iter().filter_map(|command| match command {
+            Command::RapidMove { x, y, .. }
+            | Command::LinearMove { x, y, .. }
+            | Command::Arc { x, y, .. } => Some(Vector2::new(*x, *y)),
+            _ => None,
+        });
+
+        let first = points.next()?;
+        let (min, max) = points.fold((first, first), |(min, max), point| {
+            (
+                Vector2::new(min.x.min(point.x), min.y.min(point.y)),
+                Vector2::new(max.x.max(point.x), max.y.max(point.y)),
+            )
+        });
+
+        Some(Self { min, max })
+    }
+
+    fn width(&self) -> f32 {
+        self.max.x - self.min.x
+    }
+
+    fn height(&self) -> f32 {
+        self.max.y - self.min.y
+    }
+}
+
+impl Origin {
+    /// The bottom-left corner `bounds` should be translated to sit at, for this origin choice.
+    fn target_bottom_left(self, workspace: WorkspaceSize, bounds: BoundingBox) -> Vector2<f32> {
+        match self {
+            Origin::BottomLeft => Vector2::new(0.0, 0.0),
+            Origin::Center => Vector2::new(
+                (workspace.width.get::<millimeter>() - bounds.width()) / 2.0,
+                (workspace.height.get::<millimeter>() - bounds.height()) / 2.0,
+            ),
+            Origin::Offset { x, y } => Vector2::new(x.get::<millimeter>(), y.get::<millimeter>()),
+        }
+    }
+}
+
+/// Translates every motion command's endpoint in `commands` by `translation`. Arc center offsets
+/// are relative to the arc's own start point, so they don't need to move.
+fn translate(commands: &mut [Command], translation: Vector2<f32>) {
+    for command in commands {
+        match command {
+            Command::RapidMove { x, y, .. }
+            | Command::LinearMove { x, y, .. }
+            | Command::Arc { x, y, .. } => {
+                *x += translation.x;
+                *y += translation.y;
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Optionally repositions `commands` to the stage's chosen `origin`, then checks the result
+/// against `workspace`, bailing with the offending stage and overflow amount if it still doesn't
+/// fit. Stages that emit no motion commands are trivially fine.
+pub fn fit_to_workspace(
+    commands: &mut [Command],
+    workspace: WorkspaceSize,
+    origin: Origin,
+    auto_fit: bool,
+    stage_description: &str,
+) -> Result<()> {
+    let Some(bounds) = BoundingBox::of(commands) else {
+        return Ok(());
+    };
+
+    if auto_fit {
+        let target = origin.target_bottom_left(workspace, bounds);
+        let translation = target - bounds.min;
+        translate(commands, translation);
+    }
+
+    let bounds = BoundingBox::of(commands).expect("just computed a bounding box for these commands");
+
+    let width = workspace.width.get::<millimeter>();
+    let height = workspace.height.get::<millimeter>();
+
+    let overflow_x = (-bounds.min.x).max(bounds.max.x - width).max(0.0);
+    let overflow_y = (-bounds.min.y).max(bounds.max.y - height).max(0.0);
+
+    if overflow_x > 0.0 || overflow_y > 0.0 {
+        bail!(
+            "{stage_description} doesn't fit the machine's {width:.1}x{height:.1}mm workspace: \
+             toolpath spans X[{:.1}, {:.1}] Y[{:.1}, {:.1}], overflowing by {overflow_x:.2}mm in X \
+             and {overflow_y:.2}mm in Y.",
+            bounds.min.x,
+            bounds.max.x,
+            bounds.min.y,
+            bounds.max.y,
+        );
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use uom::si::length::Length;
+
+    use super::*;
+
+    fn workspace(width_mm: f32, height_mm: f32) -> WorkspaceSize {
+        WorkspaceSize {
+            width: Length::new::<millimeter>(width_mm),
+            height: Length::new::<millimeter>(height_mm),
+        }
+    }
+
+    fn moves(points: &[(f32, f32)]) -> Vec<Command> {
+        points
+            .iter()
+            .map(|&(x, y)| Command::RapidMove { x, y, z: 1.0 })
+            .collect()
+    }
+
+    #[test]
+    fn bounding_box_of_ignores_non_motion_commands() {
+        let mut commands = moves(&[(1.0, 2.0), (5.0, -3.0)]);
+        commands.push(Command::ToolOff);
+
+        let bounds = BoundingBox::of(&commands).unwrap();
+        assert_eq!(bounds.min, Vector2::new(1.0, -3.0));
+        assert_eq!(bounds.max, Vector2::new(5.0, 2.0));
+    }
+
+    #[test]
+    fn bounding_box_of_is_none_for_only_non_motion_commands() {
+        assert!(BoundingBox::of(&[Command::ToolOff]).is_none());
+    }
+
+    #[test]
+    fn fit_to_workspace_translates_to_bottom_left_when_auto_fit() {
+        let mut commands = moves(&[(5.0, 5.0), (10.0, 10.0)]);
+        fit_to_workspace(&mut commands, workspace(100.0, 100.0), Origin::BottomLeft, true, "test stage").unwrap();
+
+        let bounds = BoundingBox::of(&commands).unwrap();
+        assert_eq!(bounds.min, Vector2::new(0.0, 0.0));
+        assert_eq!(bounds.max, Vector2::new(5.0, 5.0));
+    }
+
+    #[test]
+    fn fit_to_workspace_centers_when_origin_is_center() {
+        let mut commands = moves(&[(0.0, 0.0), (10.0, 20.0)]);
+        fit_to_workspace(&mut commands, workspace(100.0, 100.0), Origin::Center, true, "test stage").unwrap();
+
+        let bounds = BoundingBox::of(&commands).unwrap();
+        assert!((bounds.min.x - 45.0).abs() < 1e-4);
+        assert!((bounds.min.y - 40.0).abs() < 1e-4);
+    }
+
+    #[test]
+    fn fit_to_workspace_bails_when_toolpath_overflows_without_auto_fit() {
+        let mut commands = moves(&[(0.0, 0.0), (200.0, 0.0)]);
+        let result = fit_to_workspace(&mut commands, workspace(100.0, 100.0), Origin::BottomLeft, false, "test stage");
+
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn fit_to_workspace_is_a_no_op_for_commands_with_no_motion() {
+        let mut commands = vec![Command::ToolOff];
+        fit_to_workspace(&mut commands, workspace(100.0, 100.0), Origin::BottomLeft, true, "test stage").unwrap();
+    }
+}